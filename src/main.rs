@@ -1,28 +1,106 @@
 use clap::Parser;
 use git2::Repository;
 use rayon::prelude::*;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Built-in target groups, named by language/toolchain.
+///
+/// This is the default set merged with (and overridable by) the user's
+/// `~/.config/cactus/targets.toml`.
+fn builtin_groups() -> BTreeMap<String, Vec<String>> {
+    let mut groups = BTreeMap::new();
+    groups.insert(
+        "java".to_string(),
+        vec!["build".to_string(), ".gradle".to_string()],
+    );
+    groups.insert(
+        "dotnet".to_string(),
+        vec!["bin".to_string(), "obj".to_string()],
+    );
+    groups.insert("node".to_string(), vec!["node_modules".to_string()]);
+    groups.insert("rust".to_string(), vec!["target".to_string()]);
+    groups.insert(
+        "python".to_string(),
+        vec![
+            "__pycache__".to_string(),
+            ".mypy_cache".to_string(),
+            ".pytest_cache".to_string(),
+            ".ruff_cache".to_string(),
+            ".tox".to_string(),
+        ],
+    );
+    groups
+}
 
-const TARGETS: &[&str] = &[
-    // Java / Gradle / Kotlin
-    "build",
-    ".gradle",
-    // .NET / generic
-    "bin",
-    "obj",
-    // Node
-    "node_modules",
-    // Rust
-    "target",
-    // Python
-    "__pycache__",
-    ".mypy_cache",
-    ".pytest_cache",
-    ".ruff_cache",
-    ".tox",
-];
+#[derive(Deserialize, Default)]
+struct TargetsFile {
+    #[serde(flatten)]
+    groups: BTreeMap<String, Vec<String>>,
+}
+
+/// Merge the built-in groups with `~/.config/cactus/targets.toml`, if present.
+/// User groups with the same name replace the built-in ones; new names are added.
+fn load_groups() -> BTreeMap<String, Vec<String>> {
+    let mut groups = builtin_groups();
+
+    let Some(home) = dirs::home_dir() else {
+        return groups;
+    };
+    let path = home.join(".config").join("cactus").join("targets.toml");
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return groups;
+    };
+    match toml::from_str::<TargetsFile>(&contents) {
+        Ok(file) => groups.extend(file.groups),
+        Err(e) => eprintln!("cactus: ignoring invalid {}: {e}", path.display()),
+    }
+    groups
+}
+
+/// Resolve the active set of directory names to purge, applying `--type`/`--type-not`.
+///
+/// Errors if `include` or `exclude` names a group that isn't in `groups`, so a typo
+/// like `--type bulid` doesn't silently resolve to an empty, no-op target set.
+fn active_targets(
+    groups: &BTreeMap<String, Vec<String>>,
+    include: &[String],
+    exclude: &[String],
+) -> Result<HashSet<String>, String> {
+    for name in include.iter().chain(exclude.iter()) {
+        if !groups.contains_key(name) {
+            let available: Vec<&str> = groups.keys().map(String::as_str).collect();
+            return Err(format!(
+                "cactus: unknown target group '{name}' (available: {})",
+                available.join(", ")
+            ));
+        }
+    }
+
+    let selected: Vec<&String> = if include.is_empty() {
+        groups.keys().collect()
+    } else {
+        include.iter().collect()
+    };
+
+    let mut names = HashSet::new();
+    for group in selected {
+        if exclude.contains(group) {
+            continue;
+        }
+        if let Some(dirs) = groups.get(group) {
+            names.extend(dirs.iter().cloned());
+        }
+    }
+    Ok(names)
+}
 
 #[derive(Parser)]
 #[command(about = "Purge gitignored build artifacts and caches")]
@@ -41,6 +119,42 @@ struct Args {
     /// Skip confirmation prompt
     #[arg(short, long)]
     yes: bool,
+
+    /// Purge every top-level directory git considers ignored, not just the active target groups
+    #[arg(long)]
+    all_ignored: bool,
+
+    /// Restrict to these target groups (comma-separated, e.g. "rust,node")
+    #[arg(long, value_delimiter = ',')]
+    r#type: Vec<String>,
+
+    /// Exclude these target groups (comma-separated)
+    #[arg(long = "type-not", value_delimiter = ',')]
+    type_not: Vec<String>,
+
+    /// Move purged directories to the OS trash instead of deleting them permanently
+    #[arg(long)]
+    trash: bool,
+
+    /// Only purge directories whose newest file is older than this many days
+    #[arg(long = "older-than", value_name = "DAYS")]
+    older_than: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+enum DeleteMethod {
+    /// Permanently remove with `fs::remove_dir_all`
+    Remove,
+    /// Move to the OS recycle bin via the `trash` crate
+    Trash,
+}
+
+#[derive(Clone, Copy)]
+enum ScanMode<'a> {
+    /// Directory names from the active target groups, matched against the repo's ignore rules
+    Targets(&'a HashSet<String>),
+    /// Any directory the repo considers ignored, wherever it is
+    AllIgnored,
 }
 
 struct Purge {
@@ -48,6 +162,15 @@ struct Purge {
     size: u64,
 }
 
+/// A lightweight snapshot sent from the scan workers to the progress reporter.
+struct ProgressData {
+    repos_scanned: usize,
+    dirs_found: usize,
+    bytes_measured: u64,
+}
+
+const PROGRESS_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
 fn find_repos(base: &Path, max_depth: usize) -> Vec<PathBuf> {
     let mut repos = Vec::new();
     collect_repos(base, max_depth, 0, &mut repos);
@@ -59,6 +182,8 @@ fn collect_repos(dir: &Path, max_depth: usize, depth: usize, repos: &mut Vec<Pat
     if depth > max_depth {
         return;
     }
+    // `.git` is a directory in a normal repo, but a gitlink file in a submodule
+    // or a linked worktree; `exists()` recognizes both as a repo root.
     if dir.join(".git").exists() {
         repos.push(dir.to_path_buf());
         return;
@@ -74,8 +199,16 @@ fn collect_repos(dir: &Path, max_depth: usize, depth: usize, repos: &mut Vec<Pat
     }
 }
 
-fn dir_size(path: &Path) -> u64 {
+/// Total size and newest file modification time under a directory.
+struct DirStats {
+    size: u64,
+    /// `None` means the directory has no files and counts as infinitely old.
+    newest_mtime: Option<SystemTime>,
+}
+
+fn dir_stats(path: &Path) -> DirStats {
     let mut total = 0u64;
+    let mut newest: Option<SystemTime> = None;
     let mut stack = vec![path.to_path_buf()];
     while let Some(dir) = stack.pop() {
         let Ok(entries) = fs::read_dir(&dir) else {
@@ -86,24 +219,94 @@ fn dir_size(path: &Path) -> u64 {
             if ft.is_dir() && !ft.is_symlink() {
                 stack.push(entry.path());
             } else if ft.is_file() {
-                total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                let Ok(meta) = entry.metadata() else { continue };
+                total += meta.len();
+                if let Ok(modified) = meta.modified() {
+                    newest = Some(match newest {
+                        Some(n) if n >= modified => n,
+                        _ => modified,
+                    });
+                }
             }
         }
     }
-    total
+    DirStats {
+        size: total,
+        newest_mtime: newest,
+    }
 }
 
-fn find_purgeable(repo_path: &Path) -> Vec<Purge> {
+fn find_purgeable(
+    repo_path: &Path,
+    mode: ScanMode<'_>,
+    older_than: Option<SystemTime>,
+) -> Vec<Purge> {
     let Ok(repo) = Repository::open(repo_path) else {
         return Vec::new();
     };
 
     let mut results = Vec::new();
-    scan_dir(&repo, repo_path, repo_path, &mut results);
+    let cache = IgnoreCache::new(repo_path);
+    scan_repo(&repo, repo_path, mode, older_than, &cache, &mut results);
     results
 }
 
-fn scan_dir(repo: &Repository, repo_root: &Path, dir: &Path, out: &mut Vec<Purge>) {
+/// Scan a repo's working tree, then recurse into each checked-out submodule
+/// with its own `Repository` so submodule-local `.gitignore` rules apply.
+fn scan_repo(
+    repo: &Repository,
+    repo_root: &Path,
+    mode: ScanMode<'_>,
+    older_than: Option<SystemTime>,
+    cache: &IgnoreCache,
+    out: &mut Vec<Purge>,
+) {
+    let submodule_paths: HashSet<PathBuf> = repo
+        .submodules()
+        .map(|subs| subs.iter().map(|s| repo_root.join(s.path())).collect())
+        .unwrap_or_default();
+
+    scan_dir(
+        repo_root,
+        repo_root,
+        mode,
+        older_than,
+        &submodule_paths,
+        cache,
+        out,
+    );
+
+    let Ok(submodules) = repo.submodules() else {
+        return;
+    };
+    for submodule in submodules {
+        let submodule_path = repo_root.join(submodule.path());
+        if !submodule_path.is_dir() {
+            continue; // not checked out
+        }
+        let Ok(submodule_repo) = submodule.open() else {
+            continue;
+        };
+        scan_repo(
+            &submodule_repo,
+            &submodule_path,
+            mode,
+            older_than,
+            cache,
+            out,
+        );
+    }
+}
+
+fn scan_dir(
+    repo_root: &Path,
+    dir: &Path,
+    mode: ScanMode<'_>,
+    older_than: Option<SystemTime>,
+    submodule_paths: &HashSet<PathBuf>,
+    cache: &IgnoreCache,
+    out: &mut Vec<Purge>,
+) {
     let Ok(entries) = fs::read_dir(dir) else {
         return;
     };
@@ -115,24 +318,211 @@ fn scan_dir(repo: &Repository, repo_root: &Path, dir: &Path, out: &mut Vec<Purge
         let name = entry.file_name();
         let name = name.to_str().unwrap_or("");
 
-        if TARGETS.contains(&name) {
-            let rel = path.strip_prefix(repo_root).unwrap_or(&path);
-            let check = format!("{}/", rel.display());
-            if repo.is_path_ignored(Path::new(&check)).unwrap_or(false) {
-                out.push(Purge {
-                    path: path.clone(),
-                    size: dir_size(&path),
-                });
-            }
+        // Submodules are scanned separately in scan_repo, with their own
+        // ignore rules, so don't descend into them here.
+        if submodule_paths.contains(&path) {
             continue;
         }
 
-        // Skip .git and other hidden dirs
-        if name.starts_with('.') {
-            continue;
+        match mode {
+            ScanMode::Targets(targets) => {
+                if targets.contains(name) {
+                    try_push_purge(repo_root, &path, older_than, cache, out);
+                    continue;
+                }
+
+                // Skip .git and other hidden dirs
+                if name.starts_with('.') {
+                    continue;
+                }
+                scan_dir(
+                    repo_root,
+                    &path,
+                    mode,
+                    older_than,
+                    submodule_paths,
+                    cache,
+                    out,
+                );
+            }
+            ScanMode::AllIgnored => {
+                // .git itself is never a purge candidate, ignored or not
+                if name == ".git" {
+                    continue;
+                }
+                if try_push_purge(repo_root, &path, older_than, cache, out) {
+                    continue;
+                }
+                scan_dir(
+                    repo_root,
+                    &path,
+                    mode,
+                    older_than,
+                    submodule_paths,
+                    cache,
+                    out,
+                );
+            }
+        }
+    }
+}
+
+/// Builds and caches the per-directory ignore matchers a repo scan needs,
+/// plus the global `core.excludesFile` matcher, so a deep/wide tree parses
+/// each `.gitignore` at most once no matter how many descendants are
+/// checked against it.
+struct IgnoreCache {
+    per_dir: RefCell<HashMap<PathBuf, Option<ignore::gitignore::Gitignore>>>,
+    global: Option<ignore::gitignore::Gitignore>,
+}
+
+impl IgnoreCache {
+    fn new(repo_root: &Path) -> Self {
+        IgnoreCache {
+            per_dir: RefCell::new(HashMap::new()),
+            global: load_global_gitignore(repo_root),
+        }
+    }
+
+    /// Whether `path` is ignored, honoring nested `.gitignore` files (and
+    /// `.git/info/exclude`) the way git itself does: the deepest file that
+    /// matches wins, so a `!`-prefixed whitelist pattern in a nested
+    /// `.gitignore` can re-include something a shallower one ignores.
+    /// Walking stops at `repo_root` — this never crosses into a parent repo.
+    /// Falls back to the global `core.excludesFile` matcher only when no
+    /// directory in between has an opinion either way.
+    fn is_ignored(&self, repo_root: &Path, path: &Path) -> bool {
+        let mut dir = path.parent();
+        while let Some(d) = dir {
+            if let Some(ignored) = self.match_dir(d, d == repo_root, path) {
+                return ignored;
+            }
+            if d == repo_root {
+                break;
+            }
+            dir = d.parent();
+        }
+        self.global
+            .as_ref()
+            .and_then(|m| match_against(m, path))
+            .unwrap_or(false)
+    }
+
+    /// Matches `path` against the cached matcher for `dir`, building and
+    /// caching it on first use. Returns `None` when `dir` has no rules (or
+    /// none of them mention `path`), so the caller keeps walking up.
+    fn match_dir(&self, dir: &Path, is_repo_root: bool, path: &Path) -> Option<bool> {
+        let mut per_dir = self.per_dir.borrow_mut();
+        let matcher = per_dir
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| build_gitignore_at(dir, is_repo_root));
+        matcher.as_ref().and_then(|m| match_against(m, path))
+    }
+}
+
+/// Builds the ignore matcher rooted at `dir` (its `.gitignore`, plus
+/// `.git/info/exclude` when `dir` is the repo root). Returns `None` if
+/// neither file exists.
+fn build_gitignore_at(dir: &Path, is_repo_root: bool) -> Option<ignore::gitignore::Gitignore> {
+    let mut builder = ignore::gitignore::GitignoreBuilder::new(dir);
+    let mut has_rules = false;
+
+    let gitignore_path = dir.join(".gitignore");
+    if gitignore_path.is_file() {
+        builder.add(&gitignore_path);
+        has_rules = true;
+    }
+    if is_repo_root {
+        let exclude_path = dir.join(".git").join("info").join("exclude");
+        if exclude_path.is_file() {
+            builder.add(&exclude_path);
+            has_rules = true;
+        }
+    }
+    if !has_rules {
+        return None;
+    }
+    builder.build().ok()
+}
+
+/// Loads git's global excludes file — `core.excludesFile` if set, otherwise
+/// its documented default of `$XDG_CONFIG_HOME/git/ignore` (`~/.config/git/ignore`
+/// when unset) — so directories ignored only globally (e.g. editor swap dirs)
+/// are still recognized, matching what `repo.is_path_ignored` used to honor.
+fn load_global_gitignore(repo_root: &Path) -> Option<ignore::gitignore::Gitignore> {
+    let path = global_excludes_path(repo_root)?;
+    if !path.is_file() {
+        return None;
+    }
+    let mut builder = ignore::gitignore::GitignoreBuilder::new("/");
+    builder.add(&path);
+    builder.build().ok()
+}
+
+fn global_excludes_path(repo_root: &Path) -> Option<PathBuf> {
+    // Run inside repo_root so a repo-local override of core.excludesFile
+    // (rather than the user's --global one) is honored too.
+    if let Ok(output) = std::process::Command::new("git")
+        .args(["config", "--get", "core.excludesFile"])
+        .current_dir(repo_root)
+        .output()
+    {
+        if output.status.success() {
+            let configured = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !configured.is_empty() {
+                return Some(expand_tilde(&configured));
+            }
         }
-        scan_dir(repo, repo_root, &path, out);
     }
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("git").join("ignore"))
+}
+
+fn expand_tilde(raw: &str) -> PathBuf {
+    match raw.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or_else(|| PathBuf::from(raw), |home| home.join(rest)),
+        None => PathBuf::from(raw),
+    }
+}
+
+fn match_against(matcher: &ignore::gitignore::Gitignore, path: &Path) -> Option<bool> {
+    match matcher.matched(path, true) {
+        ignore::Match::Ignore(_) => Some(true),
+        ignore::Match::Whitelist(_) => Some(false),
+        ignore::Match::None => None,
+    }
+}
+
+/// If `path` is ignored, records it as a `Purge` (unless `older_than` excludes it
+/// as too recently touched). Returns whether `path` was ignored, so callers know
+/// not to recurse into it either way.
+fn try_push_purge(
+    repo_root: &Path,
+    path: &Path,
+    older_than: Option<SystemTime>,
+    cache: &IgnoreCache,
+    out: &mut Vec<Purge>,
+) -> bool {
+    if !cache.is_ignored(repo_root, path) {
+        return false;
+    }
+
+    let stats = dir_stats(path);
+    let stale = match (older_than, stats.newest_mtime) {
+        (None, _) => true,
+        (Some(_), None) => true,
+        (Some(cutoff), Some(newest)) => newest <= cutoff,
+    };
+    if stale {
+        out.push(Purge {
+            path: path.to_path_buf(),
+            size: stats.size,
+        });
+    }
+    true
 }
 
 fn human_size(bytes: u64) -> String {
@@ -150,6 +540,79 @@ fn human_size(bytes: u64) -> String {
     }
 }
 
+/// Scan `repos` in parallel, reporting live progress to stderr when it's a TTY.
+fn scan_with_progress(
+    repos: &[PathBuf],
+    mode: ScanMode<'_>,
+    older_than: Option<SystemTime>,
+) -> Vec<(PathBuf, Vec<Purge>)> {
+    let repos_scanned = AtomicUsize::new(0);
+    let dirs_found = AtomicUsize::new(0);
+    let bytes_measured = AtomicU64::new(0);
+
+    let reporter = io::stderr().is_terminal().then(|| {
+        let (tx, rx) = crossbeam_channel::unbounded::<ProgressData>();
+        let handle = thread::spawn(move || {
+            let mut last_paint = Instant::now() - PROGRESS_REPAINT_INTERVAL;
+            let mut last = None;
+            for data in rx.iter() {
+                if last_paint.elapsed() >= PROGRESS_REPAINT_INTERVAL {
+                    paint_progress(&data);
+                    last_paint = Instant::now();
+                }
+                last = Some(data);
+            }
+            if let Some(data) = last {
+                paint_progress(&data);
+            }
+            eprint!("\r\x1b[2K");
+            io::stderr().flush().ok();
+        });
+        (tx, handle)
+    });
+
+    let all_purges: Vec<(PathBuf, Vec<Purge>)> = repos
+        .par_iter()
+        .map(|r| {
+            let purges = find_purgeable(r, mode, older_than);
+
+            let scanned = repos_scanned.fetch_add(1, Ordering::Relaxed) + 1;
+            let found = dirs_found.fetch_add(purges.len(), Ordering::Relaxed) + purges.len();
+            let measured_bytes: u64 = purges.iter().map(|p| p.size).sum();
+            let measured =
+                bytes_measured.fetch_add(measured_bytes, Ordering::Relaxed) + measured_bytes;
+
+            if let Some((tx, _)) = &reporter {
+                let _ = tx.send(ProgressData {
+                    repos_scanned: scanned,
+                    dirs_found: found,
+                    bytes_measured: measured,
+                });
+            }
+
+            (r.clone(), purges)
+        })
+        .filter(|(_, p)| !p.is_empty())
+        .collect();
+
+    if let Some((tx, handle)) = reporter {
+        drop(tx);
+        handle.join().ok();
+    }
+
+    all_purges
+}
+
+fn paint_progress(data: &ProgressData) {
+    eprint!(
+        "\r\x1b[2Kscanning… {} repos, {} dirs, {} measured",
+        data.repos_scanned,
+        data.dirs_found,
+        human_size(data.bytes_measured)
+    );
+    io::stderr().flush().ok();
+}
+
 fn run(args: Args) -> Result<(), String> {
     let base = args
         .path
@@ -161,11 +624,19 @@ fn run(args: Args) -> Result<(), String> {
         return Err(format!("No git repos found in {}", base.display()));
     }
 
-    let all_purges: Vec<(PathBuf, Vec<Purge>)> = repos
-        .par_iter()
-        .map(|r| (r.clone(), find_purgeable(r)))
-        .filter(|(_, p)| !p.is_empty())
-        .collect();
+    let groups = load_groups();
+    let targets = active_targets(&groups, &args.r#type, &args.type_not)?;
+    let mode = if args.all_ignored {
+        ScanMode::AllIgnored
+    } else {
+        ScanMode::Targets(&targets)
+    };
+
+    let older_than = args
+        .older_than
+        .map(|days| SystemTime::now() - Duration::from_secs(days * 86_400));
+
+    let all_purges = scan_with_progress(&repos, mode, older_than);
 
     if all_purges.is_empty() {
         println!("Nothing to purge.");
@@ -180,7 +651,11 @@ fn run(args: Args) -> Result<(), String> {
         println!("\x1b[1m{}\x1b[0m", rel.display());
         for p in purges {
             let dir_rel = p.path.strip_prefix(repo).unwrap_or(&p.path);
-            println!("  \x1b[31m{}\x1b[0m  {}", dir_rel.display(), human_size(p.size));
+            println!(
+                "  \x1b[31m{}\x1b[0m  {}",
+                dir_rel.display(),
+                human_size(p.size)
+            );
             total_size += p.size;
             total_count += 1;
         }
@@ -208,11 +683,17 @@ fn run(args: Args) -> Result<(), String> {
         }
     }
 
+    let method = if args.trash {
+        DeleteMethod::Trash
+    } else {
+        DeleteMethod::Remove
+    };
+
     let mut freed = 0u64;
     let mut errors = 0usize;
     for (_, purges) in &all_purges {
         for p in purges {
-            match fs::remove_dir_all(&p.path) {
+            match delete_purge(&p.path, method) {
                 Ok(()) => freed += p.size,
                 Err(e) => {
                     eprintln!("cactus: {}: {e}", p.path.display());
@@ -222,13 +703,24 @@ fn run(args: Args) -> Result<(), String> {
         }
     }
 
-    println!("Freed {}", human_size(freed));
+    println!(
+        "{} {}",
+        if args.trash { "Trashed" } else { "Freed" },
+        human_size(freed)
+    );
     if errors > 0 {
         return Err(format!("{errors} dirs failed to remove"));
     }
     Ok(())
 }
 
+fn delete_purge(path: &Path, method: DeleteMethod) -> Result<(), String> {
+    match method {
+        DeleteMethod::Remove => fs::remove_dir_all(path).map_err(|e| e.to_string()),
+        DeleteMethod::Trash => trash::delete(path).map_err(|e| e.to_string()),
+    }
+}
+
 fn main() {
     unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
     if let Err(e) = run(Args::parse()) {
@@ -250,6 +742,16 @@ mod tests {
             .current_dir(&dir)
             .status()
             .unwrap();
+        Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        Command::new("git")
+            .args(["config", "user.name", "Test"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
         Command::new("git")
             .args(["commit", "--allow-empty", "-m", "init", "-q"])
             .current_dir(&dir)
@@ -261,6 +763,36 @@ mod tests {
         dir
     }
 
+    fn default_targets() -> HashSet<String> {
+        active_targets(&builtin_groups(), &[], &[]).unwrap()
+    }
+
+    #[test]
+    fn active_targets_restricts_to_included_groups() {
+        let groups = builtin_groups();
+        let names = active_targets(&groups, &["rust".to_string()], &[]).unwrap();
+        assert_eq!(names, HashSet::from(["target".to_string()]));
+    }
+
+    #[test]
+    fn active_targets_drops_excluded_groups() {
+        let groups = builtin_groups();
+        let names = active_targets(
+            &groups,
+            &["rust".to_string(), "node".to_string()],
+            &["node".to_string()],
+        )
+        .unwrap();
+        assert_eq!(names, HashSet::from(["target".to_string()]));
+    }
+
+    #[test]
+    fn active_targets_rejects_unknown_group_name() {
+        let groups = builtin_groups();
+        let err = active_targets(&groups, &["bulid".to_string()], &[]).unwrap_err();
+        assert!(err.contains("bulid"));
+    }
+
     #[test]
     fn purges_gitignored_build_dir() {
         let tmp = tempfile::tempdir().unwrap();
@@ -268,7 +800,7 @@ mod tests {
         fs::create_dir_all(repo.join("build")).unwrap();
         fs::write(repo.join("build/out.jar"), "fake").unwrap();
 
-        let purges = find_purgeable(&repo);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), None);
         assert_eq!(purges.len(), 1);
         assert!(purges[0].path.ends_with("build"));
     }
@@ -281,7 +813,7 @@ mod tests {
         fs::create_dir_all(repo.join("build")).unwrap();
         fs::write(repo.join("build/out.jar"), "fake").unwrap();
 
-        let purges = find_purgeable(&repo);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), None);
         assert!(purges.is_empty());
     }
 
@@ -296,7 +828,7 @@ mod tests {
         )
         .unwrap();
 
-        let purges = find_purgeable(&repo);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), None);
         assert_eq!(purges.len(), 1);
         assert!(purges[0].path.ends_with("node_modules"));
     }
@@ -309,19 +841,155 @@ mod tests {
         fs::create_dir_all(repo.join("node_modules")).unwrap();
         fs::create_dir_all(repo.join("target")).unwrap();
 
-        let purges = find_purgeable(&repo);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), None);
         assert_eq!(purges.len(), 3);
     }
 
     #[test]
-    fn dir_size_computes_correctly() {
+    fn all_ignored_mode_purges_outside_targets_list() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = init_repo(tmp.path(), "proj", ".venv/\ndist/\n");
+        fs::create_dir_all(repo.join(".venv")).unwrap();
+        fs::create_dir_all(repo.join("dist")).unwrap();
+
+        // Targets mode doesn't know about .venv/dist
+        assert!(find_purgeable(&repo, ScanMode::Targets(&default_targets()), None).is_empty());
+
+        let purges = find_purgeable(&repo, ScanMode::AllIgnored, None);
+        assert_eq!(purges.len(), 2);
+    }
+
+    #[test]
+    fn all_ignored_mode_never_purges_dot_git() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = init_repo(tmp.path(), "proj", "");
+
+        let purges = find_purgeable(&repo, ScanMode::AllIgnored, None);
+        assert!(purges.is_empty());
+    }
+
+    #[test]
+    fn nested_gitignore_negation_overrides_parent_ignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = init_repo(tmp.path(), "proj", "cache/\n");
+        fs::create_dir_all(repo.join("sub/cache")).unwrap();
+        fs::write(repo.join("sub/cache/out.bin"), "fake").unwrap();
+        fs::write(repo.join("sub/.gitignore"), "!cache/\n").unwrap();
+
+        // The root .gitignore ignores cache/, but sub/.gitignore whitelists
+        // it back — matching real git's deepest-file-wins precedence.
+        let purges = find_purgeable(&repo, ScanMode::AllIgnored, None);
+        assert!(purges.is_empty());
+    }
+
+    #[test]
+    fn scans_submodule_with_its_own_gitignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sub = init_repo(tmp.path(), "sublib", "build/\n");
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(&sub)
+            .status()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "add gitignore", "-q"])
+            .current_dir(&sub)
+            .status()
+            .unwrap();
+
+        // Parent repo has no gitignore of its own, so it doesn't know
+        // the submodule's build/ dir is ignored.
+        let parent = init_repo(tmp.path(), "parent", "");
+        Command::new("git")
+            .args([
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                sub.to_str().unwrap(),
+                "vendor/sublib",
+            ])
+            .current_dir(&parent)
+            .status()
+            .unwrap();
+
+        let checked_out = parent.join("vendor/sublib");
+        fs::create_dir_all(checked_out.join("build")).unwrap();
+        fs::write(checked_out.join("build/out.o"), "fake").unwrap();
+
+        let purges = find_purgeable(&parent, ScanMode::Targets(&default_targets()), None);
+        assert_eq!(purges.len(), 1);
+        assert!(purges[0].path.ends_with("build"));
+    }
+
+    #[test]
+    fn dir_stats_computes_size_and_newest_mtime() {
         let tmp = tempfile::tempdir().unwrap();
         let dir = tmp.path().join("test");
         fs::create_dir_all(dir.join("sub")).unwrap();
         fs::write(dir.join("a.txt"), "hello").unwrap(); // 5 bytes
         fs::write(dir.join("sub/b.txt"), "world!").unwrap(); // 6 bytes
 
-        assert_eq!(dir_size(&dir), 11);
+        let stats = dir_stats(&dir);
+        assert_eq!(stats.size, 11);
+        assert!(stats.newest_mtime.is_some());
+    }
+
+    #[test]
+    fn dir_stats_empty_dir_has_no_mtime() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("empty");
+        fs::create_dir_all(&dir).unwrap();
+
+        let stats = dir_stats(&dir);
+        assert_eq!(stats.size, 0);
+        assert!(stats.newest_mtime.is_none());
+    }
+
+    #[test]
+    fn older_than_excludes_recently_touched_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = init_repo(tmp.path(), "proj", "build/\n");
+        fs::create_dir_all(repo.join("build")).unwrap();
+        fs::write(repo.join("build/out.jar"), "fake").unwrap();
+
+        // The file was just written, so a 1-day threshold should exclude it.
+        let cutoff = SystemTime::now() - Duration::from_secs(86_400);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), Some(cutoff));
+        assert!(purges.is_empty());
+    }
+
+    #[test]
+    fn older_than_keeps_empty_dirs_as_infinitely_old() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = init_repo(tmp.path(), "proj", "build/\n");
+        fs::create_dir_all(repo.join("build")).unwrap();
+
+        let cutoff = SystemTime::now() - Duration::from_secs(86_400);
+        let purges = find_purgeable(&repo, ScanMode::Targets(&default_targets()), Some(cutoff));
+        assert_eq!(purges.len(), 1);
+    }
+
+    #[test]
+    fn delete_purge_remove_deletes_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("victim");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("f.txt"), "hi").unwrap();
+
+        delete_purge(&dir, DeleteMethod::Remove).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn delete_purge_trash_moves_directory_away() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("victim");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("f.txt"), "hi").unwrap();
+
+        delete_purge(&dir, DeleteMethod::Trash).unwrap();
+        assert!(!dir.exists());
     }
 
     #[test]